@@ -0,0 +1,87 @@
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Arc, Weak};
+
+use patch_db::PatchDb;
+
+use crate::db::DatabaseModel;
+use crate::manager::health_breaker::HealthCheckBreakerRegistry;
+use crate::manager::health_queue::HealthCheckQueue;
+use crate::manager::health_token_pool::HealthProbeTokenPool;
+use crate::manager::health_wal::{self, HealthTransitionWal};
+use crate::manager::health_worker::HealthCheckWorkerManager;
+use crate::Error;
+
+/// Shared state handed to every RPC handler and background task. Cheap to clone: every field is
+/// itself reference-counted, so cloning `RpcContext` never deep-copies worker/registry state.
+#[derive(Clone)]
+pub struct RpcContext(Arc<RpcContextInner>);
+
+impl Deref for RpcContext {
+    type Target = RpcContextInner;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// A non-owning handle to an [`RpcContext`]. Anything `RpcContext` itself owns (directly or
+/// transitively) must hold one of these rather than a real `RpcContext`/`Arc<RpcContextInner>` —
+/// a strong ref stored back inside the context it came from is a self-reference that keeps
+/// `RpcContextInner` alive forever.
+#[derive(Clone)]
+pub struct RpcContextWeak(Weak<RpcContextInner>);
+
+impl RpcContextWeak {
+    pub fn upgrade(&self) -> Option<RpcContext> {
+        self.0.upgrade().map(RpcContext)
+    }
+}
+
+pub struct RpcContextInner {
+    pub db: PatchDb,
+    pub health_worker_manager: HealthCheckWorkerManager,
+    pub health_breakers: HealthCheckBreakerRegistry,
+    pub health_token_pool: HealthProbeTokenPool,
+    pub health_wal: HealthTransitionWal,
+    pub health_queue: HealthCheckQueue,
+}
+
+impl RpcContext {
+    /// `probe_parallelism` is the `-j`-style cap on health probes in flight at once.
+    /// `health_wal_path` is where the dependency-transition write-ahead log is persisted; any
+    /// entries left unfinalized by a prior unclean shutdown are replayed before this returns.
+    pub async fn init(
+        db: PatchDb,
+        probe_parallelism: usize,
+        health_wal_path: PathBuf,
+    ) -> Result<Self, Error> {
+        let ctx = Self(Arc::new(RpcContextInner {
+            db,
+            health_worker_manager: HealthCheckWorkerManager::new(),
+            health_breakers: HealthCheckBreakerRegistry::new(),
+            health_token_pool: HealthProbeTokenPool::new(probe_parallelism),
+            health_wal: HealthTransitionWal::load(health_wal_path).await?,
+            health_queue: HealthCheckQueue::new(),
+        }));
+
+        ctx.health_queue.attach(ctx.clone()).await;
+
+        let mut db_handle = ctx.db.handle();
+        health_wal::replay(&ctx, &mut db_handle, &ctx.health_wal).await?;
+
+        // Resume health checking for every package that was already installed before this
+        // restart. Beyond this point it's the install/uninstall lifecycle's job to call
+        // `ensure_started`/`stop` as packages come and go.
+        let installed = DatabaseModel::new().package_data().keys(&mut db_handle).await?;
+        for id in installed {
+            ctx.health_worker_manager.ensure_started(&ctx, &id).await;
+        }
+
+        Ok(ctx)
+    }
+
+    /// A non-owning handle, safe for a field of `RpcContextInner` itself to hold without leaking.
+    pub fn downgrade(&self) -> RpcContextWeak {
+        RpcContextWeak(Arc::downgrade(&self.0))
+    }
+}
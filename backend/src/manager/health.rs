@@ -113,17 +113,40 @@ pub async fn check<Db: DbHandle>(
 
     let health_results = if let Some(started) = started {
         tracing::debug!("Checking health of {}", id);
-        manifest
-            .health_checks
-            .check_all(
-                ctx,
-                &manifest.containers,
-                started,
-                id,
-                &manifest.version,
-                &manifest.volumes,
-            )
-            .await?
+
+        let mut tripped_ids = Vec::new();
+        let mut checks_to_run = manifest.health_checks.clone();
+        for check_id in manifest.health_checks.0.keys() {
+            if ctx.health_breakers.should_short_circuit(id, check_id).await {
+                tripped_ids.push(check_id.clone());
+                checks_to_run.0.remove(check_id);
+            }
+        }
+
+        let priority = ctx.health_token_pool.priority_for(id);
+        let mut health_results = {
+            let _token = ctx.health_token_pool.acquire(priority).await;
+            checks_to_run
+                .check_all(
+                    ctx,
+                    &manifest.containers,
+                    started,
+                    id,
+                    &manifest.version,
+                    &manifest.volumes,
+                )
+                .await?
+        };
+
+        for (check_id, result) in health_results.iter() {
+            ctx.health_breakers.record(id, check_id, result).await;
+        }
+        for check_id in tripped_ids {
+            let synthesized = ctx.health_breakers.synthesized_result(id, &check_id).await;
+            health_results.insert(check_id, synthesized);
+        }
+
+        health_results
     } else {
         return Ok(());
     };
@@ -175,30 +198,64 @@ pub async fn check<Db: DbHandle>(
 
     let receipts = crate::dependencies::BreakTransitiveReceipts::new(&mut tx).await?;
 
-    for (dependent, info) in (current_dependents).0.iter() {
-        let failures: BTreeMap<HealthCheckId, HealthCheckResult> = health_results
-            .iter()
-            .filter(|(_, hc_res)| !matches!(hc_res, HealthCheckResult::Success { .. }))
-            .filter(|(hc_id, _)| info.health_checks.contains(hc_id))
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-
-        if !failures.is_empty() {
-            break_transitive(
-                &mut tx,
-                &dependent,
-                id,
-                DependencyError::HealthChecksFailed { failures },
-                &mut BTreeMap::new(),
-                &receipts,
-            )
-            .await?;
-        } else {
-            heal_transitive(ctx, &mut tx, &dependent, id, &receipts.dependency_receipt).await?;
+    let intended: Vec<(PackageId, Option<DependencyError>)> = (current_dependents)
+        .0
+        .iter()
+        .map(|(dependent, info)| {
+            let failures: BTreeMap<HealthCheckId, HealthCheckResult> = health_results
+                .iter()
+                .filter(|(_, hc_res)| !matches!(hc_res, HealthCheckResult::Success { .. }))
+                .filter(|(hc_id, _)| info.health_checks.contains(hc_id))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            let target = if failures.is_empty() {
+                None
+            } else {
+                Some(DependencyError::HealthChecksFailed { failures })
+            };
+            (dependent.clone(), target)
+        })
+        .collect();
+
+    // A package with no dependents has nothing to log; skip the WAL entirely for it. Every
+    // dependent is logged otherwise, whether or not its target changed.
+    let logged = if intended.is_empty() {
+        Vec::new()
+    } else {
+        ctx.health_wal.begin(id, &intended).await?
+    };
+
+    let mut any_dependent_broken = false;
+    for entry in &logged {
+        match &entry.target {
+            Some(error) => {
+                any_dependent_broken = true;
+                break_transitive(
+                    &mut tx,
+                    &entry.dependent,
+                    id,
+                    error.clone(),
+                    &mut BTreeMap::new(),
+                    &receipts,
+                )
+                .await?;
+            }
+            None => {
+                heal_transitive(ctx, &mut tx, &entry.dependent, id, &receipts.dependency_receipt).await?;
+            }
         }
     }
 
+    if any_dependent_broken {
+        ctx.health_token_pool.mark_broken(id);
+    } else {
+        ctx.health_token_pool.mark_healthy(id);
+    }
+
     tx.save().await?;
+    ctx.health_wal
+        .finalize(&logged.iter().map(|e| e.seq).collect::<Vec<_>>())
+        .await?;
 
     Ok(())
 }
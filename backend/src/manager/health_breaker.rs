@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use tokio::time::Instant;
+
+use crate::s9pk::manifest::PackageId;
+use crate::status::health_check::{HealthCheckId, HealthCheckResult};
+
+/// Consecutive failures required before a breaker trips.
+const FAILURE_THRESHOLD: u32 = 3;
+/// Cooldown applied the first time a breaker trips.
+const INITIAL_COOLDOWN: Duration = Duration::from_secs(30);
+/// Cooldown is doubled on every re-trip, capped at this value.
+const MAX_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+/// Prefix on a synthesized failure's error message, so it reads differently from a failure a live
+/// probe just produced — dependents can tell "still probing" apart from "breaker open" by content.
+const BREAKER_OPEN_PREFIX: &str = "circuit breaker open";
+
+/// Per-(package, health check) circuit breaker state: once a check has failed
+/// `FAILURE_THRESHOLD` times in a row, stop invoking its probe until a cooldown elapses, returning
+/// the last known failure instead.
+struct CircuitBreaker {
+    tripped: AtomicBool,
+    consecutive_failures: AtomicU32,
+    last_error: RwLock<String>,
+    cooldown: RwLock<Duration>,
+    tripped_at: RwLock<Option<Instant>>,
+}
+
+impl CircuitBreaker {
+    fn new() -> Self {
+        Self {
+            tripped: AtomicBool::new(false),
+            consecutive_failures: AtomicU32::new(0),
+            last_error: RwLock::new(String::new()),
+            cooldown: RwLock::new(INITIAL_COOLDOWN),
+            tripped_at: RwLock::new(None),
+        }
+    }
+
+    /// `true` once the cooldown since tripping has elapsed, allowing a single half-open probe.
+    async fn cooldown_elapsed(&self) -> bool {
+        match *self.tripped_at.read().await {
+            Some(at) => at.elapsed() >= *self.cooldown.read().await,
+            None => true,
+        }
+    }
+
+    async fn trip(&self, error: String) {
+        self.tripped.store(true, Ordering::SeqCst);
+        *self.last_error.write().await = error;
+        *self.tripped_at.write().await = Some(Instant::now());
+    }
+
+    async fn record_success(&self) {
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+        if self.tripped.swap(false, Ordering::SeqCst) {
+            *self.cooldown.write().await = INITIAL_COOLDOWN;
+            *self.tripped_at.write().await = None;
+        }
+    }
+
+    async fn record_failure(&self, error: String) {
+        let was_tripped = self.tripped.load(Ordering::SeqCst);
+        if was_tripped {
+            // A half-open probe just failed: re-trip and back off further.
+            let doubled = (*self.cooldown.read().await * 2).min(MAX_COOLDOWN);
+            *self.cooldown.write().await = doubled;
+            self.trip(error).await;
+            return;
+        }
+
+        let failures = self.consecutive_failures.fetch_add(1, Ordering::SeqCst) + 1;
+        *self.last_error.write().await = error.clone();
+        if failures >= FAILURE_THRESHOLD {
+            self.trip(error).await;
+        }
+    }
+
+    async fn synthesize(&self) -> HealthCheckResult {
+        HealthCheckResult::Failure {
+            error: format!("{BREAKER_OPEN_PREFIX}: {}", self.last_error.read().await),
+        }
+    }
+}
+
+/// Tracks a [`CircuitBreaker`] for every `(PackageId, HealthCheckId)` pair seen so far.
+#[derive(Default)]
+pub struct HealthCheckBreakerRegistry {
+    breakers: RwLock<BTreeMap<(PackageId, HealthCheckId), Arc<CircuitBreaker>>>,
+}
+
+impl HealthCheckBreakerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn get_or_insert(&self, package: &PackageId, check: &HealthCheckId) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self
+            .breakers
+            .read()
+            .await
+            .get(&(package.clone(), check.clone()))
+        {
+            return breaker.clone();
+        }
+        self.breakers
+            .write()
+            .await
+            .entry((package.clone(), check.clone()))
+            .or_insert_with(|| Arc::new(CircuitBreaker::new()))
+            .clone()
+    }
+
+    /// `true` if the given check is tripped and still within its cooldown window, meaning the
+    /// real probe should be skipped this cycle in favor of the breaker's synthesized result.
+    pub async fn should_short_circuit(&self, package: &PackageId, check: &HealthCheckId) -> bool {
+        let breaker = self.get_or_insert(package, check).await;
+        breaker.tripped.load(Ordering::SeqCst) && !breaker.cooldown_elapsed().await
+    }
+
+    /// The failure to report for `check` in place of invoking its probe, tagged so dependents can
+    /// tell it apart from a fresh failure coming from a live probe.
+    pub async fn synthesized_result(&self, package: &PackageId, check: &HealthCheckId) -> HealthCheckResult {
+        self.get_or_insert(package, check).await.synthesize().await
+    }
+
+    /// Feed a real probe result back into the breaker for `check`, tripping or resetting it.
+    pub async fn record(&self, package: &PackageId, check: &HealthCheckId, result: &HealthCheckResult) {
+        match result {
+            HealthCheckResult::Failure { error } => self.record_failure(package, check, error.clone()).await,
+            _ => self.record_success(package, check).await,
+        }
+    }
+
+    pub async fn record_success(&self, package: &PackageId, check: &HealthCheckId) {
+        self.get_or_insert(package, check).await.record_success().await
+    }
+
+    pub async fn record_failure(&self, package: &PackageId, check: &HealthCheckId, error: String) {
+        self.get_or_insert(package, check).await.record_failure(error).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_util::{check_id, pkg as package_id};
+
+    #[tokio::test(start_paused = true)]
+    async fn trips_after_threshold_consecutive_failures() {
+        let registry = HealthCheckBreakerRegistry::new();
+        let pkg = package_id("foo");
+        let chk = check_id("main");
+
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            registry.record_failure(&pkg, &chk, "down".to_owned()).await;
+            assert!(!registry.should_short_circuit(&pkg, &chk).await);
+        }
+
+        registry.record_failure(&pkg, &chk, "down".to_owned()).await;
+        assert!(registry.should_short_circuit(&pkg, &chk).await);
+
+        let synthesized = registry.synthesized_result(&pkg, &chk).await;
+        match synthesized {
+            HealthCheckResult::Failure { error } => assert!(error.starts_with(BREAKER_OPEN_PREFIX)),
+            _ => panic!("expected a synthesized failure"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn half_open_success_resets_breaker() {
+        let registry = HealthCheckBreakerRegistry::new();
+        let pkg = package_id("foo");
+        let chk = check_id("main");
+
+        for _ in 0..FAILURE_THRESHOLD {
+            registry.record_failure(&pkg, &chk, "down".to_owned()).await;
+        }
+        assert!(registry.should_short_circuit(&pkg, &chk).await);
+
+        tokio::time::advance(INITIAL_COOLDOWN + Duration::from_secs(1)).await;
+        assert!(!registry.should_short_circuit(&pkg, &chk).await);
+
+        registry.record_success(&pkg, &chk).await;
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            registry.record_failure(&pkg, &chk, "down".to_owned()).await;
+            assert!(!registry.should_short_circuit(&pkg, &chk).await);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn half_open_failure_re_trips_and_doubles_cooldown() {
+        let registry = HealthCheckBreakerRegistry::new();
+        let pkg = package_id("foo");
+        let chk = check_id("main");
+
+        for _ in 0..FAILURE_THRESHOLD {
+            registry.record_failure(&pkg, &chk, "down".to_owned()).await;
+        }
+
+        tokio::time::advance(INITIAL_COOLDOWN + Duration::from_secs(1)).await;
+        assert!(!registry.should_short_circuit(&pkg, &chk).await);
+
+        registry.record_failure(&pkg, &chk, "still down".to_owned()).await;
+        assert!(registry.should_short_circuit(&pkg, &chk).await);
+
+        // The doubled cooldown hasn't elapsed yet even though the initial one would have.
+        tokio::time::advance(INITIAL_COOLDOWN + Duration::from_secs(1)).await;
+        assert!(registry.should_short_circuit(&pkg, &chk).await);
+
+        tokio::time::advance(INITIAL_COOLDOWN * 2).await;
+        assert!(!registry.should_short_circuit(&pkg, &chk).await);
+    }
+}
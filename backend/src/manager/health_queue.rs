@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use futures::FutureExt;
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+use crate::context::{RpcContext, RpcContextWeak};
+use crate::manager::health;
+use crate::s9pk::manifest::PackageId;
+use crate::Error;
+
+/// A single enqueued request to run [`health::check`] for a package, stamped with a global,
+/// strictly increasing id so jobs for the same package can never be applied out of order.
+struct HealthCheckJob {
+    id: u64,
+    package: PackageId,
+    done: oneshot::Sender<HealthCheckOutcome>,
+}
+
+/// How a queued job for [`health::check`] resolved. A closed channel instead means the job was
+/// superseded (see [`HealthCheckQueue::enqueue`]), not that it panicked.
+pub enum HealthCheckOutcome {
+    Success,
+    Failure(Error),
+    Panicked(String),
+}
+
+/// Routes every health-check run through a single shared queue so that, for a given package,
+/// jobs are dequeued and applied strictly in submission order by one worker. This replaces the
+/// previous best-effort `should_commit` flag with a principled ordering guarantee: a job whose id
+/// is older than what's already been committed for that package is dropped instead of applied.
+pub struct HealthCheckQueue {
+    // A weak handle, not a real `RpcContext`: this queue lives inside `RpcContextInner` itself, so
+    // a strong ref here would be a self-reference that keeps it alive forever.
+    ctx: Arc<RwLock<Option<RpcContextWeak>>>,
+    next_job_id: AtomicU64,
+    /// The id of the last job actually applied for each package.
+    applied: Arc<RwLock<BTreeMap<PackageId, u64>>>,
+    senders: RwLock<BTreeMap<PackageId, mpsc::UnboundedSender<HealthCheckJob>>>,
+}
+
+impl HealthCheckQueue {
+    pub fn new() -> Self {
+        Self {
+            ctx: Arc::new(RwLock::new(None)),
+            next_job_id: AtomicU64::new(0),
+            applied: Arc::new(RwLock::new(BTreeMap::new())),
+            senders: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Must be called once, after the owning `RpcContext` exists, before any job can be applied.
+    pub async fn attach(&self, ctx: RpcContext) {
+        *self.ctx.write().await = Some(ctx.downgrade());
+    }
+
+    /// Enqueue a health check for `package`, spawning its dedicated ordering worker on first use.
+    /// The returned receiver resolves once the job has been applied (or dropped as stale, which
+    /// surfaces as a closed channel rather than an error).
+    pub async fn enqueue(&self, package: &PackageId) -> oneshot::Receiver<HealthCheckOutcome> {
+        let (done, rx) = oneshot::channel();
+        let id = self.next_job_id.fetch_add(1, Ordering::SeqCst);
+        let job = HealthCheckJob {
+            id,
+            package: package.clone(),
+            done,
+        };
+
+        let mut senders = self.senders.write().await;
+        let sender = senders
+            .entry(package.clone())
+            .or_insert_with(|| self.spawn_worker(package.clone()));
+
+        // A send failure just means the worker exited; the next enqueue spawns a fresh one.
+        if sender.send(job).is_err() {
+            senders.remove(package);
+        }
+
+        rx
+    }
+
+    fn spawn_worker(&self, package: PackageId) -> mpsc::UnboundedSender<HealthCheckJob> {
+        let (tx, mut rx) = mpsc::unbounded_channel::<HealthCheckJob>();
+        let applied = self.applied.clone();
+        let ctx = self.ctx.clone();
+
+        tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                // Coalesce: if newer jobs for this package are already buffered, jump straight to
+                // the freshest one instead of applying stale intermediate results. Superseded
+                // jobs' senders are simply dropped, signalling "superseded" to their waiters.
+                let job = coalesce_to_latest(first, &mut rx);
+
+                let last_applied = *applied.read().await.get(&job.package).unwrap_or(&0);
+                if is_stale(job.id, last_applied) {
+                    continue;
+                }
+
+                let Some(weak) = ctx.read().await.clone() else {
+                    continue;
+                };
+                // A failed upgrade means the daemon is shutting down and `RpcContext` itself has
+                // already been dropped; there's no longer anywhere to apply this job.
+                let Some(ctx) = weak.upgrade() else {
+                    break;
+                };
+
+                let should_commit = AtomicBool::new(true);
+                let mut db = ctx.db.handle();
+                // Catch a panic from `health::check` here rather than letting it take down this
+                // package's ordering worker task: an uncaught panic would drop `job.done` without
+                // sending, which is indistinguishable from a job being coalesced away as stale.
+                let outcome = match AssertUnwindSafe(health::check(
+                    &ctx,
+                    &mut db,
+                    &job.package,
+                    &should_commit,
+                ))
+                .catch_unwind()
+                .await
+                {
+                    Ok(Ok(())) => HealthCheckOutcome::Success,
+                    Ok(Err(e)) => {
+                        tracing::error!("Queued health check for {} failed: {}", job.package, e);
+                        HealthCheckOutcome::Failure(e)
+                    }
+                    Err(panic) => {
+                        let msg = panic
+                            .downcast_ref::<&str>()
+                            .map(|s| s.to_string())
+                            .or_else(|| panic.downcast_ref::<String>().cloned())
+                            .unwrap_or_else(|| "unknown panic".to_owned());
+                        tracing::error!("Queued health check for {} panicked: {}", job.package, msg);
+                        HealthCheckOutcome::Panicked(msg)
+                    }
+                };
+
+                applied.write().await.insert(job.package.clone(), job.id);
+                let _ = job.done.send(outcome);
+            }
+        });
+
+        tx
+    }
+}
+
+impl Default for HealthCheckQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Drain any jobs already buffered behind `first`, keeping only the freshest one. Pulled out of
+/// [`HealthCheckQueue::spawn_worker`] so the coalescing logic can be exercised without a
+/// `RpcContext`.
+fn coalesce_to_latest(
+    first: HealthCheckJob,
+    rx: &mut mpsc::UnboundedReceiver<HealthCheckJob>,
+) -> HealthCheckJob {
+    let mut latest = first;
+    while let Ok(newer) = rx.try_recv() {
+        latest = newer;
+    }
+    latest
+}
+
+/// A job is stale if a fresher job for the same package has already been applied.
+fn is_stale(job_id: u64, last_applied: u64) -> bool {
+    job_id < last_applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_util::pkg;
+
+    fn job(id: u64, done: oneshot::Sender<HealthCheckOutcome>) -> HealthCheckJob {
+        HealthCheckJob {
+            id,
+            package: pkg("foo"),
+            done,
+        }
+    }
+
+    #[test]
+    fn is_stale_rejects_jobs_older_than_the_last_applied_one() {
+        assert!(is_stale(0, 1));
+        assert!(!is_stale(1, 1));
+        assert!(!is_stale(2, 1));
+    }
+
+    #[tokio::test]
+    async fn coalesce_to_latest_drains_buffered_jobs_and_keeps_the_freshest() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let (done0, rx0) = oneshot::channel();
+        let (done1, rx1) = oneshot::channel();
+        let (done2, _rx2) = oneshot::channel();
+
+        tx.send(job(1, done1)).unwrap();
+        tx.send(job(2, done2)).unwrap();
+        let first = job(0, done0);
+
+        let latest = coalesce_to_latest(first, &mut rx);
+        assert_eq!(latest.id, 2);
+
+        // The superseded jobs' senders were dropped along with them, so their waiters observe a
+        // closed channel rather than any outcome.
+        assert!(rx0.await.is_err());
+        assert!(rx1.await.is_err());
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::Mutex;
+
+use tokio::sync::oneshot;
+
+use crate::s9pk::manifest::PackageId;
+
+/// How eagerly a package's probe should be scheduled relative to others waiting on a token.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenPriority {
+    /// At least one of this package's dependents currently carries a health-related
+    /// `DependencyError`, so recovery should be detected as fast as possible.
+    High,
+    Normal,
+}
+
+struct PoolState {
+    available: usize,
+    high_priority_waiters: VecDeque<oneshot::Sender<()>>,
+    low_priority_waiters: VecDeque<oneshot::Sender<()>>,
+}
+
+/// Bounds the number of health probes in flight across every installed package to `limit` at a
+/// time. Tokens are handed out FIFO within a priority class, with high-priority waiters always
+/// served ahead of normal ones.
+pub struct HealthProbeTokenPool {
+    state: Mutex<PoolState>,
+    broken: Mutex<BTreeSet<PackageId>>,
+}
+
+impl HealthProbeTokenPool {
+    /// `limit` mirrors a `-j`-style configured parallelism: the max number of probes in flight.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            state: Mutex::new(PoolState {
+                available: limit,
+                high_priority_waiters: VecDeque::new(),
+                low_priority_waiters: VecDeque::new(),
+            }),
+            broken: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Record that `id`'s health is currently implicated in a dependent's `DependencyError`, so
+    /// its next probe should be prioritized to detect recovery quickly.
+    pub fn mark_broken(&self, id: &PackageId) {
+        self.broken.lock().unwrap().insert(id.clone());
+    }
+
+    /// Record that none of `id`'s dependents currently have a health-related `DependencyError`.
+    pub fn mark_healthy(&self, id: &PackageId) {
+        self.broken.lock().unwrap().remove(id);
+    }
+
+    pub fn priority_for(&self, id: &PackageId) -> TokenPriority {
+        if self.broken.lock().unwrap().contains(id) {
+            TokenPriority::High
+        } else {
+            TokenPriority::Normal
+        }
+    }
+
+    /// Acquire a token, blocking until one is available. Always release the returned guard (by
+    /// letting it drop) even if the probe it guards errors.
+    pub async fn acquire(&self, priority: TokenPriority) -> HealthProbeToken<'_> {
+        let rx = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                None
+            } else {
+                let (tx, rx) = oneshot::channel();
+                match priority {
+                    TokenPriority::High => state.high_priority_waiters.push_back(tx),
+                    TokenPriority::Normal => state.low_priority_waiters.push_back(tx),
+                }
+                Some(rx)
+            }
+        };
+
+        if let Some(rx) = rx {
+            // A release() transferred a permit directly to us; the channel firing *is* the grant.
+            let _ = rx.await;
+        }
+
+        HealthProbeToken { pool: self }
+    }
+
+    /// Hand the freed token to the next waiter, or bank it if there are none. A waiter's
+    /// `oneshot::Receiver` can already be gone by the time we reach it (its `acquire()` future was
+    /// dropped — e.g. a future `select!`/timeout around it), in which case `send` fails and the
+    /// token would otherwise vanish; keep trying subsequent waiters until one actually accepts it
+    /// or the queues are empty, so a cancelled waiter can never shrink the pool.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let next = state
+                .high_priority_waiters
+                .pop_front()
+                .or_else(|| state.low_priority_waiters.pop_front());
+            match next {
+                Some(tx) => {
+                    if tx.send(()).is_ok() {
+                        return;
+                    }
+                }
+                None => {
+                    state.available += 1;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// RAII guard for a held token; returns it to the pool on drop.
+pub struct HealthProbeToken<'a> {
+    pool: &'a HealthProbeTokenPool,
+}
+
+impl Drop for HealthProbeToken<'_> {
+    fn drop(&mut self) {
+        self.pool.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn high_priority_waiters_are_served_before_normal_ones() {
+        let pool = Arc::new(HealthProbeTokenPool::new(1));
+        let held = pool.acquire(TokenPriority::Normal).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        let low = tokio::spawn({
+            let pool = pool.clone();
+            let order = order.clone();
+            async move {
+                let _token = pool.acquire(TokenPriority::Normal).await;
+                order.lock().unwrap().push("low");
+            }
+        });
+        tokio::task::yield_now().await;
+
+        let high = tokio::spawn({
+            let pool = pool.clone();
+            let order = order.clone();
+            async move {
+                let _token = pool.acquire(TokenPriority::High).await;
+                order.lock().unwrap().push("high");
+            }
+        });
+        tokio::task::yield_now().await;
+
+        drop(held);
+
+        high.await.unwrap();
+        low.await.unwrap();
+
+        assert_eq!(*order.lock().unwrap(), vec!["high", "low"]);
+    }
+
+    #[tokio::test]
+    async fn cancelling_a_queued_waiter_does_not_leak_its_token() {
+        let pool = Arc::new(HealthProbeTokenPool::new(1));
+        let held = pool.acquire(TokenPriority::Normal).await;
+
+        let cancelled = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                let _token = pool.acquire(TokenPriority::Normal).await;
+            }
+        });
+        // Let it register as a waiter before cancelling it mid-queue.
+        tokio::task::yield_now().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(held);
+
+        let fresh = tokio::time::timeout(Duration::from_millis(200), pool.acquire(TokenPriority::Normal)).await;
+        assert!(
+            fresh.is_ok(),
+            "releasing into a cancelled waiter must fall back to the next waiter or `available`"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use patch_db::DbHandle;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::context::RpcContext;
+use crate::dependencies::{break_transitive, heal_transitive, BreakTransitiveReceipts, DependencyError};
+use crate::s9pk::manifest::PackageId;
+use crate::Error;
+
+/// A single intended break/heal transition, recorded before it is applied so it can be replayed
+/// if the daemon crashes between writing health results and finishing the dependent updates.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingTransition {
+    pub seq: u64,
+    pub package: PackageId,
+    pub dependent: PackageId,
+    /// `Some` to break the dependent with this error, `None` to heal it.
+    pub target: Option<DependencyError>,
+}
+
+/// Append-only write-ahead log of in-flight dependency transitions for [`super::health::check`].
+/// Entries are written before `break_transitive`/`heal_transitive` run and only dropped from the
+/// log once the enclosing `tx.save()` has succeeded, so an unclean shutdown in between leaves a
+/// durable record of what still needs to be reconciled.
+pub struct HealthTransitionWal {
+    path: PathBuf,
+    next_seq: AtomicU64,
+    pending: Mutex<Vec<PendingTransition>>,
+}
+
+impl HealthTransitionWal {
+    pub async fn load(path: PathBuf) -> Result<Self, Error> {
+        let pending = if tokio::fs::try_exists(&path).await.unwrap_or(false) {
+            let contents = tokio::fs::read_to_string(&path).await?;
+            contents
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let next_seq = pending
+            .iter()
+            .map(|e: &PendingTransition| e.seq)
+            .max()
+            .map(|s| s + 1)
+            .unwrap_or(0);
+        Ok(Self {
+            path,
+            next_seq: AtomicU64::new(next_seq),
+            pending: Mutex::new(pending),
+        })
+    }
+
+    /// Record the intended transitions for this cycle before applying any of them.
+    pub async fn begin(
+        &self,
+        package: &PackageId,
+        transitions: &[(PackageId, Option<DependencyError>)],
+    ) -> Result<Vec<PendingTransition>, Error> {
+        if transitions.is_empty() {
+            // Nothing to log, so skip the lock + full-buffer fsync below.
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::with_capacity(transitions.len());
+        for (dependent, target) in transitions {
+            let seq = self.next_seq.fetch_add(1, Ordering::SeqCst);
+            entries.push(PendingTransition {
+                seq,
+                package: package.clone(),
+                dependent: dependent.clone(),
+                target: target.clone(),
+            });
+        }
+
+        let mut pending = self.pending.lock().await;
+        pending.extend(entries.iter().cloned());
+        self.flush(&pending).await?;
+
+        Ok(entries)
+    }
+
+    /// Drop the given entries from the log now that `tx.save()` has succeeded for them.
+    pub async fn finalize(&self, seqs: &[u64]) -> Result<(), Error> {
+        if seqs.is_empty() {
+            return Ok(());
+        }
+
+        let mut pending = self.pending.lock().await;
+        pending.retain(|entry| !seqs.contains(&entry.seq));
+        self.flush(&pending).await
+    }
+
+    async fn flush(&self, pending: &[PendingTransition]) -> Result<(), Error> {
+        let mut buf = String::new();
+        for entry in pending {
+            buf.push_str(&serde_json::to_string(entry)?);
+            buf.push('\n');
+        }
+        let tmp_path = self.path.with_extension("tmp");
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        file.write_all(buf.as_bytes()).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp_path, &self.path).await?;
+        Ok(())
+    }
+
+    pub async fn unfinalized(&self) -> Vec<PendingTransition> {
+        self.pending.lock().await.clone()
+    }
+}
+
+/// Replay any transitions left unfinalized by an unclean shutdown, reconciling dependents'
+/// `DependencyError::HealthChecksFailed` state with the last committed health results.
+pub async fn replay<Db: DbHandle>(
+    ctx: &RpcContext,
+    db: &mut Db,
+    wal: &HealthTransitionWal,
+) -> Result<(), Error> {
+    let entries = wal.unfinalized().await;
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    tracing::warn!(
+        "Replaying {} unfinalized health dependency transition(s) from the write-ahead log",
+        entries.len()
+    );
+
+    let mut tx = db.begin().await?;
+    let receipts = BreakTransitiveReceipts::new(&mut tx).await?;
+
+    let mut applied = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match &entry.target {
+            Some(error) => {
+                break_transitive(
+                    &mut tx,
+                    &entry.dependent,
+                    &entry.package,
+                    error.clone(),
+                    &mut std::collections::BTreeMap::new(),
+                    &receipts,
+                )
+                .await?;
+            }
+            None => {
+                heal_transitive(ctx, &mut tx, &entry.dependent, &entry.package, &receipts.dependency_receipt).await?;
+            }
+        }
+        applied.push(entry.seq);
+    }
+
+    tx.save().await?;
+    wal.finalize(&applied).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_util::pkg;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("health-wal-test-{}-{name}", std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn begin_persists_before_finalize_and_finalize_clears_it() {
+        let path = temp_path("roundtrip");
+        let _ = tokio::fs::remove_file(&path).await;
+
+        let wal = HealthTransitionWal::load(path.clone()).await.unwrap();
+        let logged = wal.begin(&pkg("foo"), &[(pkg("bar"), None)]).await.unwrap();
+        assert_eq!(logged.len(), 1);
+
+        // Simulate a crash: reload from disk without ever having finalized anything.
+        let reloaded = HealthTransitionWal::load(path.clone()).await.unwrap();
+        let unfinalized = reloaded.unfinalized().await;
+        assert_eq!(unfinalized.len(), 1);
+        assert_eq!(unfinalized[0].seq, logged[0].seq);
+
+        wal.finalize(&[logged[0].seq]).await.unwrap();
+        let reloaded_again = HealthTransitionWal::load(path.clone()).await.unwrap();
+        assert!(reloaded_again.unfinalized().await.is_empty());
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn next_seq_resumes_from_the_highest_persisted_entry() {
+        let path = temp_path("seq");
+        let _ = tokio::fs::remove_file(&path).await;
+
+        let wal = HealthTransitionWal::load(path.clone()).await.unwrap();
+        let first = wal.begin(&pkg("foo"), &[(pkg("bar"), None)]).await.unwrap();
+
+        let reloaded = HealthTransitionWal::load(path.clone()).await.unwrap();
+        let second = reloaded.begin(&pkg("foo"), &[(pkg("baz"), None)]).await.unwrap();
+
+        assert!(second[0].seq > first[0].seq);
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+}
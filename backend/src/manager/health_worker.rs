@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use rpc_toolkit::command;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::instrument;
+
+use crate::context::{RpcContext, RpcContextWeak};
+use crate::manager::health_queue::HealthCheckOutcome;
+use crate::s9pk::manifest::PackageId;
+use crate::Error;
+
+/// Default cadence between health probes for a freshly spawned worker.
+const DEFAULT_TRANQUILITY: Duration = Duration::from_secs(15);
+
+/// The lifecycle state of a single package's health-check worker.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "kebab-case")]
+pub enum WorkerState {
+    /// A probe is currently in flight.
+    Active,
+    /// Waiting for the next scheduled probe.
+    Idle,
+    /// The worker's loop exited due to a panic or fatal error and will not be restarted.
+    Dead { error: String },
+}
+
+/// A point-in-time snapshot of a worker, suitable for returning over RPC.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HealthCheckWorkerStatus {
+    pub package_id: PackageId,
+    pub state: WorkerState,
+    pub paused: bool,
+    pub tranquility_ms: u64,
+    pub last_run: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// A long-lived task that repeatedly drives [`super::health::check`] for one installed package.
+struct HealthCheckWorker {
+    state: Arc<RwLock<WorkerState>>,
+    paused: Arc<AtomicBool>,
+    tranquility: Arc<RwLock<Duration>>,
+    last_run: Arc<RwLock<Option<DateTime<Utc>>>>,
+    last_error: Arc<RwLock<Option<String>>>,
+    handle: JoinHandle<()>,
+}
+
+impl HealthCheckWorker {
+    fn spawn(ctx: RpcContext, id: PackageId, tranquility: Duration) -> Self {
+        let state = Arc::new(RwLock::new(WorkerState::Idle));
+        let paused = Arc::new(AtomicBool::new(false));
+        let tranquility = Arc::new(RwLock::new(tranquility));
+        let last_run = Arc::new(RwLock::new(None));
+        let last_error = Arc::new(RwLock::new(None));
+
+        // The worker lives inside `RpcContextInner`, so its task holds only a weak handle.
+        let handle = tokio::spawn(Self::run(
+            ctx.downgrade(),
+            id,
+            state.clone(),
+            paused.clone(),
+            tranquility.clone(),
+            last_run.clone(),
+            last_error.clone(),
+        ));
+
+        Self {
+            state,
+            paused,
+            tranquility,
+            last_run,
+            last_error,
+            handle,
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn run(
+        ctx: RpcContextWeak,
+        id: PackageId,
+        state: Arc<RwLock<WorkerState>>,
+        paused: Arc<AtomicBool>,
+        tranquility: Arc<RwLock<Duration>>,
+        last_run: Arc<RwLock<Option<DateTime<Utc>>>>,
+        last_error: Arc<RwLock<Option<String>>>,
+    ) {
+        loop {
+            let sleep_for = *tranquility.read().await;
+            tokio::time::sleep(sleep_for).await;
+
+            if paused.load(Ordering::SeqCst) {
+                continue;
+            }
+
+            // A failed upgrade means `RpcContext` itself has already been dropped.
+            let Some(ctx) = ctx.upgrade() else {
+                return;
+            };
+
+            *state.write().await = WorkerState::Active;
+
+            // The actual run is applied by the shared, globally-ordered queue; this just submits
+            // a tick and waits for its turn, so overlapping ticks can never land out of order.
+            let enqueued = ctx.health_queue.enqueue(&id).await;
+            let res = std::panic::AssertUnwindSafe(enqueued).catch_unwind().await;
+
+            *last_run.write().await = Some(Utc::now());
+
+            match res {
+                Ok(Ok(HealthCheckOutcome::Success)) => {
+                    *last_error.write().await = None;
+                    *state.write().await = WorkerState::Idle;
+                }
+                Ok(Ok(HealthCheckOutcome::Failure(e))) => {
+                    let msg = e.to_string();
+                    tracing::warn!("Health check worker for {} reported an error: {}", id, msg);
+                    *last_error.write().await = Some(msg.clone());
+                    *state.write().await = WorkerState::Idle;
+                }
+                Ok(Ok(HealthCheckOutcome::Panicked(msg))) => {
+                    // The check itself panicked inside the shared ordering queue. Surface this the
+                    // same way a panic in this worker's own loop would: go `Dead` and stop ticking.
+                    tracing::error!("Health check for {} panicked: {}", id, msg);
+                    *last_error.write().await = Some(msg.clone());
+                    *state.write().await = WorkerState::Dead { error: msg };
+                    return;
+                }
+                Ok(Err(_recv_err)) => {
+                    // Superseded by a fresher tick before it ran; not a failure.
+                    *state.write().await = WorkerState::Idle;
+                }
+                Err(panic) => {
+                    let msg = panic
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_else(|| "unknown panic".to_owned());
+                    tracing::error!("Health check worker for {} panicked: {}", id, msg);
+                    *last_error.write().await = Some(msg.clone());
+                    *state.write().await = WorkerState::Dead { error: msg };
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn status(&self, id: &PackageId) -> HealthCheckWorkerStatus {
+        HealthCheckWorkerStatus {
+            package_id: id.clone(),
+            state: self.state.read().await.clone(),
+            paused: self.paused.load(Ordering::SeqCst),
+            tranquility_ms: self.tranquility.read().await.as_millis() as u64,
+            last_run: *self.last_run.read().await,
+            last_error: self.last_error.read().await.clone(),
+        }
+    }
+}
+
+impl Drop for HealthCheckWorker {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Owns one [`HealthCheckWorker`] per installed package and exposes introspection/control over them.
+///
+/// Takes no `RpcContext` at construction time (it's built as one of that context's own fields, so
+/// it can't depend on it existing yet); callers pass the context into [`Self::ensure_started`].
+#[derive(Clone, Default)]
+pub struct HealthCheckWorkerManager {
+    workers: Arc<RwLock<BTreeMap<PackageId, HealthCheckWorker>>>,
+}
+
+impl HealthCheckWorkerManager {
+    pub fn new() -> Self {
+        Self {
+            workers: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    /// Start the worker for `id` if there is none yet. An existing worker, including a dead one,
+    /// is left as it is.
+    pub async fn ensure_started(&self, ctx: &RpcContext, id: &PackageId) {
+        let mut workers = self.workers.write().await;
+        workers
+            .entry(id.clone())
+            .or_insert_with(|| HealthCheckWorker::spawn(ctx.clone(), id.clone(), DEFAULT_TRANQUILITY));
+    }
+
+    pub async fn stop(&self, id: &PackageId) {
+        self.workers.write().await.remove(id);
+    }
+
+    pub async fn list(&self) -> Vec<HealthCheckWorkerStatus> {
+        let workers = self.workers.read().await;
+        let mut out = Vec::with_capacity(workers.len());
+        for (id, worker) in workers.iter() {
+            out.push(worker.status(id).await);
+        }
+        out
+    }
+
+    pub async fn pause(&self, id: &PackageId) -> Result<(), Error> {
+        let workers = self.workers.read().await;
+        let worker = workers
+            .get(id)
+            .ok_or_else(|| Error::new(eyre::eyre!("no health check worker for {id}"), crate::ErrorKind::NotFound))?;
+        worker.paused.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub async fn resume(&self, id: &PackageId) -> Result<(), Error> {
+        let workers = self.workers.read().await;
+        let worker = workers
+            .get(id)
+            .ok_or_else(|| Error::new(eyre::eyre!("no health check worker for {id}"), crate::ErrorKind::NotFound))?;
+        worker.paused.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Adjust the per-package "tranquility" knob: the interval between probes.
+    pub async fn set_tranquility(&self, id: &PackageId, interval: Duration) -> Result<(), Error> {
+        let workers = self.workers.read().await;
+        let worker = workers
+            .get(id)
+            .ok_or_else(|| Error::new(eyre::eyre!("no health check worker for {id}"), crate::ErrorKind::NotFound))?;
+        *worker.tranquility.write().await = interval;
+        Ok(())
+    }
+}
+
+#[command(subcommands(list_health_workers, pause_health_worker, resume_health_worker, set_health_worker_tranquility))]
+pub fn health_worker() -> Result<(), Error> {
+    Ok(())
+}
+
+#[command(rename = "list", rpc_only)]
+pub async fn list_health_workers(#[context] ctx: RpcContext) -> Result<Vec<HealthCheckWorkerStatus>, Error> {
+    Ok(ctx.health_worker_manager.list().await)
+}
+
+#[command(rename = "pause", rpc_only)]
+pub async fn pause_health_worker(
+    #[context] ctx: RpcContext,
+    #[arg] id: PackageId,
+) -> Result<(), Error> {
+    ctx.health_worker_manager.pause(&id).await
+}
+
+#[command(rename = "resume", rpc_only)]
+pub async fn resume_health_worker(
+    #[context] ctx: RpcContext,
+    #[arg] id: PackageId,
+) -> Result<(), Error> {
+    ctx.health_worker_manager.resume(&id).await
+}
+
+#[command(rename = "set-tranquility", rpc_only)]
+pub async fn set_health_worker_tranquility(
+    #[context] ctx: RpcContext,
+    #[arg] id: PackageId,
+    #[arg] seconds: u64,
+) -> Result<(), Error> {
+    ctx.health_worker_manager
+        .set_tranquility(&id, Duration::from_secs(seconds))
+        .await
+}
@@ -0,0 +1,20 @@
+use rpc_toolkit::command;
+
+use crate::context::RpcContext;
+use crate::Error;
+
+pub mod health;
+pub mod health_breaker;
+pub mod health_queue;
+pub mod health_token_pool;
+pub mod health_wal;
+pub mod health_worker;
+#[cfg(test)]
+pub(crate) mod test_util;
+
+/// Root of the manager-owned RPC surface. Nest this under the daemon's top-level command enum to
+/// make it reachable; it currently groups the health-check worker controls.
+#[command(subcommands(health_worker::health_worker))]
+pub fn manager(#[context] _ctx: RpcContext) -> Result<(), Error> {
+    Ok(())
+}
@@ -0,0 +1,12 @@
+#![cfg(test)]
+
+use crate::s9pk::manifest::PackageId;
+use crate::status::health_check::HealthCheckId;
+
+pub fn pkg(s: &str) -> PackageId {
+    serde_json::from_value(serde_json::Value::String(s.to_owned())).unwrap()
+}
+
+pub fn check_id(s: &str) -> HealthCheckId {
+    serde_json::from_value(serde_json::Value::String(s.to_owned())).unwrap()
+}